@@ -1,19 +1,196 @@
 use std::fmt::Debug;
 
+/// How much more input is needed before a streaming parse can make progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Needed {
+    /// The exact number of additional bytes required is not known yet.
+    Unknown,
+    /// At least this many additional bytes are required.
+    Size(usize),
+}
+
+/// The outcome of a streaming parse: a completed match, a buffer that is
+/// merely too short so far, or a hard failure that will not be fixed by
+/// feeding more bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseState<'a, E> {
+    /// The match succeeded, yielding the matched slice and the remainder.
+    Done(&'a [u8], &'a [u8]),
+    /// The input is too short to decide yet; more bytes may resolve this.
+    Incomplete(Needed),
+    /// The input can never match, no matter how many more bytes arrive.
+    Failed(E),
+}
+
+/// Finds the critical factorization point of `pattern`, as used by the
+/// Two-Way string matching algorithm: the split `(left, right) = pattern.split_at(cut)`
+/// such that `right` is the lexicographically maximal suffix of `pattern`
+/// under one of two byte orderings, and `period` is that suffix's smallest
+/// period. Runs two passes over the pattern only (not the haystack), each
+/// O(m), following Crochemore & Perrin's construction.
+fn critical_factorization(pattern: &[u8]) -> (usize, usize) {
+    let len = pattern.len() as i64;
+
+    let mut ip: i64 = -1;
+    let mut jp: i64 = 0;
+    let mut k: i64 = 1;
+    let mut p: i64 = 1;
+    while jp + k < len {
+        let a = pattern[(ip + k) as usize];
+        let b = pattern[(jp + k) as usize];
+        if a == b {
+            if k == p {
+                jp += p;
+                k = 1;
+            } else {
+                k += 1;
+            }
+        } else if a > b {
+            jp += k;
+            k = 1;
+            p = jp - ip;
+        } else {
+            ip = jp;
+            jp += 1;
+            k = 1;
+            p = 1;
+        }
+    }
+    let ms = ip;
+    let p0 = p;
+
+    let mut ip: i64 = -1;
+    let mut jp: i64 = 0;
+    let mut k: i64 = 1;
+    let mut p: i64 = 1;
+    while jp + k < len {
+        let a = pattern[(ip + k) as usize];
+        let b = pattern[(jp + k) as usize];
+        if a == b {
+            if k == p {
+                jp += p;
+                k = 1;
+            } else {
+                k += 1;
+            }
+        } else if a < b {
+            jp += k;
+            k = 1;
+            p = jp - ip;
+        } else {
+            ip = jp;
+            jp += 1;
+            k = 1;
+            p = 1;
+        }
+    }
+
+    let (cut, period) = if ip + 1 > ms + 1 { (ip, p) } else { (ms, p0) };
+    ((cut + 1) as usize, period as usize)
+}
+
+/// Finds the first occurrence of `pattern` in `haystack` using the Two-Way
+/// string matching algorithm (Crochemore & Perrin), which is O(n+m) worst
+/// case with constant extra space, unlike a naive nested scan which is
+/// O(n*m). Short patterns fall back to a plain byte scan, since the
+/// factorization overhead isn't worth it below a couple of bytes.
+fn two_way_find(haystack: &[u8], pattern: &[u8]) -> Option<usize> {
+    let len = pattern.len();
+    if len == 0 {
+        return Some(0);
+    }
+    if haystack.len() < len {
+        return None;
+    }
+    if len <= 2 {
+        return haystack.windows(len).position(|w| w == pattern);
+    }
+
+    let (cut, period) = critical_factorization(pattern);
+    let is_periodic = period + cut <= len && pattern[..cut] == pattern[period..period + cut];
+
+    let mut mem = 0usize;
+    let mut pos = 0usize;
+
+    if is_periodic {
+        loop {
+            if pos + len > haystack.len() {
+                return None;
+            }
+            let window = &haystack[pos..pos + len];
+
+            let mut i = cut.max(mem);
+            while i < len && window[i] == pattern[i] {
+                i += 1;
+            }
+            if i < len {
+                pos += i - cut + 1;
+                mem = 0;
+                continue;
+            }
+
+            let mut j = cut;
+            while j > mem && window[j - 1] == pattern[j - 1] {
+                j -= 1;
+            }
+            if j <= mem {
+                return Some(pos);
+            }
+            pos += period;
+            mem = len - period;
+        }
+    } else {
+        let shift = cut.max(len - cut) + 1;
+        loop {
+            if pos + len > haystack.len() {
+                return None;
+            }
+            let window = &haystack[pos..pos + len];
+
+            let mut i = cut;
+            while i < len && window[i] == pattern[i] {
+                i += 1;
+            }
+            if i < len {
+                pos += i - cut + 1;
+                continue;
+            }
+
+            let mut j = cut;
+            while j > 0 && window[j - 1] == pattern[j - 1] {
+                j -= 1;
+            }
+            if j == 0 {
+                return Some(pos);
+            }
+            pos += shift;
+        }
+    }
+}
+
 /// A trait that is implemented for everything that can be a sequence of bytes
 pub trait ParseHelper: AsRef<[u8]> {
-    /// Skips prefix of slice until sequence is found
-    fn take_until(&self, pattern: &[u8]) -> Result<(&[u8], &[u8]), ()> {
+    /// Skips prefix of slice until sequence is found. Returns `Incomplete`
+    /// while the sequence has not been found, since more bytes may still
+    /// complete it; never returns `Failed`
+    fn take_until_streaming(&self, pattern: &[u8]) -> ParseState<'_, ()> {
         let source = self.as_ref();
         if source.len() < pattern.len() {
-            return Err(());
+            return ParseState::Incomplete(Needed::Unknown);
         }
-        for i in 0..=source.len() - pattern.len() {
-            if source[i..].starts_with(pattern) {
-                return Ok((&source[..i], &source[i..]));
-            }
+        match two_way_find(source, pattern) {
+            Some(i) => ParseState::Done(&source[..i], &source[i..]),
+            None => ParseState::Incomplete(Needed::Unknown),
+        }
+    }
+
+    /// Skips prefix of slice until sequence is found
+    fn take_until(&self, pattern: &[u8]) -> Result<(&[u8], &[u8]), ()> {
+        match self.take_until_streaming(pattern) {
+            ParseState::Done(before, after) => Ok((before, after)),
+            ParseState::Incomplete(_) => Err(()),
+            ParseState::Failed(e) => Err(e),
         }
-        Err(())
     }
 
     /// Skips prefix of slice until sequence is found and returns provided error if not
@@ -21,13 +198,23 @@ pub trait ParseHelper: AsRef<[u8]> {
         self.take_until(pattern).map_err(|_| err)
     }
 
-    /// Returns a slice of exact length
-    fn take_exact(&self, count: usize) -> Result<(&[u8], &[u8]), ()> {
+    /// Returns a slice of exact length. Returns `Incomplete` with the number
+    /// of missing bytes when the buffer is too short; never returns `Failed`
+    fn take_exact_streaming(&self, count: usize) -> ParseState<'_, ()> {
         let source = self.as_ref();
         if source.len() < count {
-            return Err(());
+            return ParseState::Incomplete(Needed::Size(count - source.len()));
+        }
+        ParseState::Done(&source[..count], &source[count..])
+    }
+
+    /// Returns a slice of exact length
+    fn take_exact(&self, count: usize) -> Result<(&[u8], &[u8]), ()> {
+        match self.take_exact_streaming(count) {
+            ParseState::Done(exact, after) => Ok((exact, after)),
+            ParseState::Incomplete(_) => Err(()),
+            ParseState::Failed(e) => Err(e),
         }
-        Ok((&source[..count], &source[count..]))
     }
 
     /// Returns a slice of exact length and returns provided error if not
@@ -35,18 +222,28 @@ pub trait ParseHelper: AsRef<[u8]> {
         self.take_exact(count).map_err(|_| err)
     }
 
-    /// Returns a slice of the provided pattern and the rest of the slice
-    fn take_expect(&self, pattern: &[u8]) -> Result<(&[u8], &[u8]), &[u8]> {
+    /// Returns a slice of the provided pattern and the rest of the slice,
+    /// distinguishing a buffer that is merely too short from one that can
+    /// never match
+    fn take_expect_streaming(&self, pattern: &[u8]) -> ParseState<'_, &[u8]> {
         let source = self.as_ref();
+        let available = source.len().min(pattern.len());
+        if source[..available] != pattern[..available] {
+            return ParseState::Failed(source);
+        }
         if source.len() < pattern.len() {
-            return Err(source);
+            return ParseState::Incomplete(Needed::Size(pattern.len() - source.len()));
         }
-        for i in 0..pattern.len() {
-            if source[i] != pattern[i] {
-                return Err(source);
-            }
+        ParseState::Done(&source[..pattern.len()], &source[pattern.len()..])
+    }
+
+    /// Returns a slice of the provided pattern and the rest of the slice
+    fn take_expect(&self, pattern: &[u8]) -> Result<(&[u8], &[u8]), &[u8]> {
+        match self.take_expect_streaming(pattern) {
+            ParseState::Done(matching, remainder) => Ok((matching, remainder)),
+            ParseState::Incomplete(_) => Err(self.as_ref()),
+            ParseState::Failed(e) => Err(e),
         }
-        Ok((&source[..pattern.len()], &source[pattern.len()..]))
     }
 
     /// Returns a slice of the provided pattern and the rest of the slice and returns provided error if not
@@ -64,6 +261,7 @@ pub trait ParseHelper: AsRef<[u8]> {
 
     /// Returns the smallest first slice found from the start that matches the condition
     /// i.e. it runs the function until the first time it is true
+    #[deprecated(note = "O(n^2) and never tests the full slice (min_size..len is exclusive); use take_while/take_till or take_while_m_n/take_till_m_n instead")]
     fn take_smallest_err<E: Debug, F: Fn(&[u8]) -> bool>(&self, f: F, min_size: usize, err: E) -> Result<(&[u8], &[u8]), E> {
         for i in min_size..self.as_ref().len() {
             if f(&self.as_ref()[..i]) {
@@ -75,6 +273,7 @@ pub trait ParseHelper: AsRef<[u8]> {
 
     /// Returns the largest slice found from the start that matches the condition.
     /// i.e. it runs the function until the last time it is true
+    #[deprecated(note = "O(n^2) and never tests the full slice (min_size..len is exclusive); use take_while/take_till or take_while_m_n/take_till_m_n instead")]
     fn take_largest_err<E: Debug, F: Fn(&[u8]) -> bool>(&self, f: F, min_size:usize, err: E) -> Result<(&[u8], &[u8]), E> {
         let mut largest = None;
         for i in min_size..self.as_ref().len() {
@@ -84,6 +283,52 @@ pub trait ParseHelper: AsRef<[u8]> {
         };
         largest.map(|i| (&self.as_ref()[..i], &self.as_ref()[i..])).ok_or(err)
     }
+
+    /// Consumes the longest leading run of bytes satisfying `f`, in a single
+    /// linear scan. Unlike `take_smallest_err`/`take_largest_err` this never
+    /// re-runs `f` over a growing prefix, so it stays O(n).
+    fn take_while<F: Fn(u8) -> bool>(&self, f: F) -> (&[u8], &[u8]) {
+        let source = self.as_ref();
+        let mut i = 0;
+        while i < source.len() && f(source[i]) {
+            i += 1;
+        }
+        (&source[..i], &source[i..])
+    }
+
+    /// Consumes leading bytes until the first one satisfying `f`, in a single
+    /// linear scan.
+    fn take_till<F: Fn(u8) -> bool>(&self, f: F) -> (&[u8], &[u8]) {
+        self.take_while(|b| !f(b))
+    }
+
+    /// Like `take_while`, but consumes at most `max` bytes and fails with
+    /// `err` if fewer than `min` bytes matched.
+    fn take_while_m_n<E: Debug, F: Fn(u8) -> bool>(&self, min: usize, max: usize, f: F, err: E) -> Result<(&[u8], &[u8]), E> {
+        let source = self.as_ref();
+        let mut i = 0;
+        while i < source.len() && i < max && f(source[i]) {
+            i += 1;
+        }
+        if i < min {
+            return Err(err);
+        }
+        Ok((&source[..i], &source[i..]))
+    }
+
+    /// Like `take_till`, but consumes at most `max` bytes and fails with
+    /// `err` if fewer than `min` bytes were consumed before `f` matched.
+    fn take_till_m_n<E: Debug, F: Fn(u8) -> bool>(&self, min: usize, max: usize, f: F, err: E) -> Result<(&[u8], &[u8]), E> {
+        self.take_while_m_n(min, max, |b| !f(b), err)
+    }
+
+    /// Skips prefix of slice until the first occurrence of any pattern in `m`,
+    /// returning the prefix, the index of the matching pattern, and the rest
+    /// of the slice. Runs in a single O(n) pass over the input regardless of
+    /// how many patterns are in `m`, unlike calling `take_until` in a loop.
+    fn take_until_any<'a>(&'a self, m: &MultiPattern) -> Option<(&'a [u8], usize, &'a [u8])> {
+        m.scan(self.as_ref())
+    }
 }
 
 impl ParseHelper for &[u8] {}
@@ -91,6 +336,328 @@ impl ParseHelper for [u8] {}
 impl ParseHelper for Vec<u8> {}
 impl ParseHelper for &str {}
 
+const AHO_CORASICK_ROOT: usize = 0;
+
+/// A set of byte patterns precompiled into an Aho-Corasick automaton, so that
+/// [`ParseHelper::take_until_any`] can find the first occurrence of any of
+/// them in a single O(n) pass rather than re-scanning the input once per
+/// pattern.
+pub struct MultiPattern {
+    patterns: Vec<Vec<u8>>,
+    // goto[state][byte] is the next state. Missing trie edges are filled in
+    // with the target reached by following failure links, so scanning never
+    // needs to consult the failure links directly.
+    goto: Vec<[usize; 256]>,
+    // output[state] is the pattern id recognised at this state, inherited
+    // from the longest matching suffix if the state itself isn't terminal.
+    output: Vec<Option<usize>>,
+}
+
+impl MultiPattern {
+    /// Builds the automaton for the given patterns. A pattern's id is its
+    /// index in `patterns`.
+    pub fn new(patterns: &[&[u8]]) -> Self {
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|p| p.to_vec()).collect();
+
+        // Build the trie: children[state][byte] is the child reached by that
+        // byte, own[state] is the pattern id that ends exactly at this node.
+        let mut children: Vec<[Option<usize>; 256]> = vec![[None; 256]];
+        let mut own: Vec<Option<usize>> = vec![None];
+
+        for (pattern_id, pattern) in patterns.iter().enumerate() {
+            let mut state = AHO_CORASICK_ROOT;
+            for &byte in pattern {
+                state = match children[state][byte as usize] {
+                    Some(next) => next,
+                    None => {
+                        children.push([None; 256]);
+                        own.push(None);
+                        let next = children.len() - 1;
+                        children[state][byte as usize] = Some(next);
+                        next
+                    }
+                };
+            }
+            own[state] = Some(pattern_id);
+        }
+
+        // Compute failure links and the goto table with a BFS over the trie
+        // in increasing depth order, so that a node's failure link (always a
+        // strictly shallower node) is finalized before the node itself.
+        let node_count = children.len();
+        let mut fail: Vec<usize> = vec![AHO_CORASICK_ROOT; node_count];
+        let mut goto: Vec<[usize; 256]> = vec![[AHO_CORASICK_ROOT; 256]; node_count];
+        let mut output: Vec<Option<usize>> = own.clone();
+
+        let mut queue = std::collections::VecDeque::new();
+        for byte in 0..256 {
+            if let Some(next) = children[AHO_CORASICK_ROOT][byte] {
+                fail[next] = AHO_CORASICK_ROOT;
+                goto[AHO_CORASICK_ROOT][byte] = next;
+                queue.push_back(next);
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                match children[state][byte] {
+                    Some(next) => {
+                        fail[next] = goto[fail[state]][byte];
+                        output[next] = own[next].or(output[fail[next]]);
+                        goto[state][byte] = next;
+                        queue.push_back(next);
+                    }
+                    None => goto[state][byte] = goto[fail[state]][byte],
+                }
+            }
+        }
+
+        MultiPattern { patterns, goto, output }
+    }
+
+    fn scan<'a>(&self, haystack: &'a [u8]) -> Option<(&'a [u8], usize, &'a [u8])> {
+        if self.patterns.is_empty() {
+            return None;
+        }
+        let mut state = AHO_CORASICK_ROOT;
+        if let Some(pattern_id) = self.output[AHO_CORASICK_ROOT] {
+            return Some((&haystack[..0], pattern_id, haystack));
+        }
+        for (i, &byte) in haystack.iter().enumerate() {
+            state = self.goto[state][byte as usize];
+            if let Some(pattern_id) = self.output[state] {
+                let end = i + 1;
+                let start = end - self.patterns[pattern_id].len();
+                return Some((&haystack[..start], pattern_id, &haystack[end..]));
+            }
+        }
+        None
+    }
+}
+
+/// A parser that consumes a prefix of its input and produces a typed output,
+/// so callers can build declarative parsers out of the `ParseHelper` methods
+/// instead of destructuring `(matched, rest)` tuples by hand at every call
+/// site.
+pub trait Parser<'a, E> {
+    /// What a successful parse produces, alongside the unconsumed remainder.
+    type Output;
+
+    /// Runs the parser against `input`, returning the unconsumed remainder
+    /// and the parsed output, or `Err` if `input` doesn't match.
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], Self::Output), E>;
+
+    /// Transforms a successful output with `f`.
+    fn map<O2, F: Fn(Self::Output) -> O2>(self, f: F) -> Map<Self, F> where Self: Sized {
+        Map { parser: self, f }
+    }
+
+    /// Transforms a failed output with `f`.
+    fn map_err<E2, F: Fn(E) -> E2>(self, f: F) -> MapErr<Self, F, E> where Self: Sized {
+        MapErr { parser: self, f, _error: std::marker::PhantomData }
+    }
+
+    /// Sequences `self` and `next`, feeding `self`'s remainder into `next`
+    /// and pairing their outputs together.
+    fn and_then<P: Parser<'a, E>>(self, next: P) -> AndThen<Self, P> where Self: Sized {
+        AndThen { first: self, second: next }
+    }
+
+    /// Tries `self` first; if it fails, resets to the original input and
+    /// tries `other` instead. For more than two alternatives, see [`alt`].
+    fn or<P: Parser<'a, E, Output = Self::Output>>(self, other: P) -> Or<Self, P> where Self: Sized {
+        Or { first: self, second: other }
+    }
+
+    /// Repeats `self` until it fails or succeeds without consuming input,
+    /// collecting the output of every repetition that consumed input.
+    /// Never fails itself; zero repetitions yields an empty `Vec`.
+    fn many0(self) -> Many0<Self> where Self: Sized {
+        Many0 { parser: self }
+    }
+
+    /// Like `many0`, but fails unless at least one repetition succeeds.
+    fn many1(self) -> Many1<Self> where Self: Sized {
+        Many1 { parser: self }
+    }
+
+    /// Turns a failing parse into `Ok((input, None))` instead of an error.
+    fn opt(self) -> Opt<Self> where Self: Sized {
+        Opt { parser: self }
+    }
+}
+
+impl<'a, O, E, F: Fn(&'a [u8]) -> Result<(&'a [u8], O), E>> Parser<'a, E> for F {
+    type Output = O;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], O), E> {
+        self(input)
+    }
+}
+
+/// See [`Parser::map`].
+pub struct Map<P, F> {
+    parser: P,
+    f: F,
+}
+
+impl<'a, O2, E, P: Parser<'a, E>, F: Fn(P::Output) -> O2> Parser<'a, E> for Map<P, F> {
+    type Output = O2;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], O2), E> {
+        self.parser.parse(input).map(|(rest, o)| (rest, (self.f)(o)))
+    }
+}
+
+/// See [`Parser::map_err`].
+pub struct MapErr<P, F, E> {
+    parser: P,
+    f: F,
+    // P's error type doesn't otherwise appear in this struct, but the impl
+    // below needs it pinned down to a single type per `MapErr` instance.
+    _error: std::marker::PhantomData<fn() -> E>,
+}
+
+impl<'a, E, E2, P: Parser<'a, E>, F: Fn(E) -> E2> Parser<'a, E2> for MapErr<P, F, E> {
+    type Output = P::Output;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], P::Output), E2> {
+        self.parser.parse(input).map_err(|e| (self.f)(e))
+    }
+}
+
+/// See [`Parser::and_then`].
+pub struct AndThen<P1, P2> {
+    first: P1,
+    second: P2,
+}
+
+impl<'a, E, P1: Parser<'a, E>, P2: Parser<'a, E>> Parser<'a, E> for AndThen<P1, P2> {
+    type Output = (P1::Output, P2::Output);
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], (P1::Output, P2::Output)), E> {
+        let (rest, first) = self.first.parse(input)?;
+        let (rest, second) = self.second.parse(rest)?;
+        Ok((rest, (first, second)))
+    }
+}
+
+/// See [`Parser::or`].
+pub struct Or<P1, P2> {
+    first: P1,
+    second: P2,
+}
+
+impl<'a, E, P1: Parser<'a, E>, P2: Parser<'a, E, Output = P1::Output>> Parser<'a, E> for Or<P1, P2> {
+    type Output = P1::Output;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], P1::Output), E> {
+        match self.first.parse(input) {
+            ok @ Ok(_) => ok,
+            Err(_) => self.second.parse(input),
+        }
+    }
+}
+
+/// See [`Parser::many0`].
+pub struct Many0<P> {
+    parser: P,
+}
+
+impl<'a, E, P: Parser<'a, E>> Parser<'a, E> for Many0<P> {
+    type Output = Vec<P::Output>;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], Vec<P::Output>), E> {
+        let mut rest = input;
+        let mut results = Vec::new();
+        while let Ok((next_rest, o)) = self.parser.parse(rest) {
+            // A sub-parser that matches without consuming input (e.g.
+            // `tag(b"")` or `opt` on a failing parser) would otherwise loop
+            // forever; stop as soon as a repetition makes no progress.
+            if next_rest.len() == rest.len() {
+                break;
+            }
+            rest = next_rest;
+            results.push(o);
+        }
+        Ok((rest, results))
+    }
+}
+
+/// See [`Parser::many1`].
+pub struct Many1<P> {
+    parser: P,
+}
+
+impl<'a, E, P: Parser<'a, E>> Parser<'a, E> for Many1<P> {
+    type Output = Vec<P::Output>;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], Vec<P::Output>), E> {
+        let (mut rest, first) = self.parser.parse(input)?;
+        let mut results = vec![first];
+        while let Ok((next_rest, o)) = self.parser.parse(rest) {
+            if next_rest.len() == rest.len() {
+                break;
+            }
+            rest = next_rest;
+            results.push(o);
+        }
+        Ok((rest, results))
+    }
+}
+
+/// See [`Parser::opt`].
+pub struct Opt<P> {
+    parser: P,
+}
+
+impl<'a, E, P: Parser<'a, E>> Parser<'a, E> for Opt<P> {
+    type Output = Option<P::Output>;
+
+    fn parse(&self, input: &'a [u8]) -> Result<(&'a [u8], Option<P::Output>), E> {
+        match self.parser.parse(input) {
+            Ok((rest, o)) => Ok((rest, Some(o))),
+            Err(_) => Ok((input, None)),
+        }
+    }
+}
+
+/// Builds a `Parser` that matches and consumes exactly `pattern`, yielding
+/// the matched slice.
+pub fn tag<'a>(pattern: &'a [u8]) -> impl Parser<'a, &'a [u8], Output = &'a [u8]> {
+    move |input: &'a [u8]| input.take_expect(pattern).map(|(matched, rest)| (rest, matched))
+}
+
+/// Builds a `Parser` that consumes exactly `n` bytes, yielding the taken
+/// slice. Fails with the original input if there aren't `n` bytes available.
+pub fn take<'a>(n: usize) -> impl Parser<'a, &'a [u8], Output = &'a [u8]> {
+    move |input: &'a [u8]| input.take_exact(n).map(|(taken, rest)| (rest, taken)).map_err(|_| input)
+}
+
+/// Builds a `Parser` that consumes everything up to (but not including)
+/// `pattern`, yielding the consumed prefix. Fails with the original input if
+/// `pattern` is never found.
+pub fn take_until<'a>(pattern: &'a [u8]) -> impl Parser<'a, &'a [u8], Output = &'a [u8]> {
+    move |input: &'a [u8]| input.take_until(pattern).map(|(before, after)| (after, before)).map_err(|_| input)
+}
+
+/// Tries each parser in `parsers` in order, returning the first successful
+/// result. Chaining `.or(...)` works for two alternatives, but reads
+/// awkwardly past that; `alt` is the direct equivalent for a whole list.
+/// The returned parser panics when run if `parsers` is empty.
+pub fn alt<'a, 'p, O, E>(parsers: &'p [&'p dyn Parser<'a, E, Output = O>]) -> impl Parser<'a, E, Output = O> + 'p {
+    move |input: &'a [u8]| {
+        let mut last_err = None;
+        for parser in parsers {
+            match parser.parse(input) {
+                Ok(ok) => return Ok(ok),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.expect("alt requires at least one parser"))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::ParseHelper;
@@ -108,6 +675,19 @@ mod test {
         assert_eq!(after, b"\r\n\r\n");
     }
 
+    #[test]
+    fn take_until_long_pattern() {
+        // Exercises the Two-Way search path (patterns longer than 2 bytes).
+        // "abcabd" factorizes to cut 5, period 1 and its left part does not
+        // repeat at that period, so the search takes the non-periodic branch.
+        let source = b"xxxxxxxxxxxxxxxxxabcabcabd";
+        let (before, after) = source.take_until(b"abcabd").unwrap();
+        assert_eq!(before, b"xxxxxxxxxxxxxxxxxabc");
+        assert_eq!(after, b"abcabd");
+
+        assert!(source.take_until(b"notfound").is_err());
+    }
+
     #[test]
     fn take_exact() {
         let source = b"hello world";
@@ -125,6 +705,58 @@ mod test {
     }
 
     #[test]
+    fn take_until_streaming() {
+        use crate::{Needed, ParseState};
+
+        let source = b"GET / HTTP";
+        assert_eq!(source.take_until_streaming(b"\r\n\r\n"), ParseState::Incomplete(Needed::Unknown));
+
+        let source = b"GET / HTTP/1.1\r\n\r\n";
+        let (before, after) = match source.take_until_streaming(b"\r\n\r\n") {
+            ParseState::Done(before, after) => (before, after),
+            other => panic!("expected Done, got {:?}", other),
+        };
+        assert_eq!(before, b"GET / HTTP/1.1");
+        assert_eq!(after, b"\r\n\r\n");
+    }
+
+    #[test]
+    fn take_exact_streaming() {
+        use crate::{Needed, ParseState};
+
+        let source = b"hi";
+        assert_eq!(source.take_exact_streaming(5), ParseState::Incomplete(Needed::Size(3)));
+
+        let source = b"hello world";
+        let (exact, after) = match source.take_exact_streaming(5) {
+            ParseState::Done(exact, after) => (exact, after),
+            other => panic!("expected Done, got {:?}", other),
+        };
+        assert_eq!(exact, b"hello");
+        assert_eq!(after, b" world");
+    }
+
+    #[test]
+    fn take_expect_streaming() {
+        use crate::{Needed, ParseState};
+
+        let source = b"hel";
+        assert_eq!(source.take_expect_streaming(b"hello "), ParseState::Incomplete(Needed::Size(3)));
+
+        let source = b"goodbye world";
+        assert_eq!(source.take_expect_streaming(b"hello "), ParseState::Failed(&source[..]));
+
+        let source = b"hello world";
+        let (matching, remained) = match source.take_expect_streaming(b"hello ") {
+            ParseState::Done(matching, remained) => (matching, remained),
+            other => panic!("expected Done, got {:?}", other),
+        };
+        assert_eq!(matching, b"hello ");
+        assert_eq!(remained, b"world");
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn take_smallest() {
         let source = b"aaaabbbbcccc";
         assert!(source.take_smallest_err(|s| s.starts_with(b"bbbb"), 0, ()).is_err());
@@ -135,6 +767,7 @@ mod test {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn take_largest() {
         let source = b"aaaabbbbcccc";
 
@@ -146,4 +779,147 @@ mod test {
         assert_eq!(first, b"aaaa");
         assert_eq!(second, b"bbbbcccc");
     }
+
+    #[test]
+    fn take_while() {
+        let source = b"aaaabbbbcccc";
+        let (taken, rest) = source.take_while(|b| b == b'a');
+        assert_eq!(taken, b"aaaa");
+        assert_eq!(rest, b"bbbbcccc");
+
+        let (taken, rest) = source.take_while(|b| b == b'z');
+        assert_eq!(taken, b"");
+        assert_eq!(rest, source);
+    }
+
+    #[test]
+    fn take_till() {
+        let source = b"aaaabbbbcccc";
+        let (taken, rest) = source.take_till(|b| b == b'b');
+        assert_eq!(taken, b"aaaa");
+        assert_eq!(rest, b"bbbbcccc");
+    }
+
+    #[test]
+    fn take_while_m_n() {
+        let source = b"aaaabbbbcccc";
+        let (taken, rest) = source.take_while_m_n(0, 2, |b| b == b'a', ()).unwrap();
+        assert_eq!(taken, b"aa");
+        assert_eq!(rest, b"aabbbbcccc");
+
+        assert!(source.take_while_m_n(5, 10, |b| b == b'a', ()).is_err());
+    }
+
+    #[test]
+    fn take_till_m_n() {
+        let source = b"aaaabbbbcccc";
+        let (taken, rest) = source.take_till_m_n(0, 10, |b| b == b'b', ()).unwrap();
+        assert_eq!(taken, b"aaaa");
+        assert_eq!(rest, b"bbbbcccc");
+
+        assert!(source.take_till_m_n(10, 20, |b| b == b'b', ()).is_err());
+    }
+
+    #[test]
+    fn take_until_any() {
+        use crate::MultiPattern;
+
+        let m = MultiPattern::new(&[b"\r\n\r\n", b"\n", b";"]);
+
+        let (before, id, after) = "key: value;rest".take_until_any(&m).unwrap();
+        assert_eq!(before, b"key: value");
+        assert_eq!(id, 2);
+        assert_eq!(after, b"rest");
+
+        let (before, id, after) = "a\nb".take_until_any(&m).unwrap();
+        assert_eq!(before, b"a");
+        assert_eq!(id, 1);
+        assert_eq!(after, b"b");
+
+        assert!("no delimiters here".take_until_any(&m).is_none());
+
+        let empty = MultiPattern::new(&[]);
+        assert!("anything".take_until_any(&empty).is_none());
+    }
+
+    #[test]
+    fn parser_combinators() {
+        use crate::{take_until, tag, Parser};
+
+        let method = tag(b"GET ").or(tag(b"POST "));
+        let request_line = method.and_then(take_until(b"\r\n"));
+
+        let (rest, (method, path)) = request_line.parse(b"GET /index.html\r\nHost: x\r\n\r\n").unwrap();
+        assert_eq!(method, b"GET ");
+        assert_eq!(path, b"/index.html");
+        assert_eq!(rest, b"\r\nHost: x\r\n\r\n");
+
+        let (_, (method, path)) = request_line.parse(b"POST /submit\r\n\r\n").unwrap();
+        assert_eq!(method, b"POST ");
+        assert_eq!(path, b"/submit");
+
+        assert!(request_line.parse(b"PUT /oops\r\n").is_err());
+    }
+
+    #[test]
+    fn parser_alt() {
+        use crate::{alt, tag, Parser};
+
+        let get = tag(b"GET ");
+        let post = tag(b"POST ");
+        let delete = tag(b"DELETE ");
+        let parsers: Vec<&dyn Parser<'_, &[u8], Output = &[u8]>> = vec![&get, &post, &delete];
+
+        let (rest, method) = alt(&parsers).parse(b"POST /submit").unwrap();
+        assert_eq!(method, b"POST ");
+        assert_eq!(rest, b"/submit");
+
+        assert!(alt(&parsers).parse(b"PUT /oops").is_err());
+    }
+
+    #[test]
+    fn parser_map_many_opt() {
+        use crate::{tag, Parser};
+
+        fn digit(input: &[u8]) -> Result<(&[u8], u8), ()> {
+            match input.first() {
+                Some(&b) if b.is_ascii_digit() => Ok((&input[1..], b)),
+                _ => Err(()),
+            }
+        }
+
+        let (rest, digits) = digit.many1().parse(b"123abc").unwrap();
+        assert_eq!(digits, vec![b'1', b'2', b'3']);
+        assert_eq!(rest, b"abc");
+
+        assert!(digit.many1().parse(b"abc").is_err());
+
+        let (rest, digits) = digit.many0().parse(b"abc").unwrap();
+        assert!(digits.is_empty());
+        assert_eq!(rest, b"abc");
+
+        let counted = digit.many1().map(|ds| ds.len());
+        let (_, count) = counted.parse(b"42x").unwrap();
+        assert_eq!(count, 2);
+
+        let (rest, maybe) = tag(b"!").opt().parse(b"no bang").unwrap();
+        assert_eq!(maybe, None);
+        assert_eq!(rest, b"no bang");
+    }
+
+    #[test]
+    fn many_stops_on_zero_width_match() {
+        use crate::{tag, Parser};
+
+        // A sub-parser that matches without consuming input must not spin
+        // forever; many0/many1 should stop as soon as a repetition makes no
+        // progress instead of looping.
+        let (rest, matches) = tag(b"").many0().parse(b"abc").unwrap();
+        assert!(matches.is_empty());
+        assert_eq!(rest, b"abc");
+
+        let (rest, matches) = tag(b"").many1().parse(b"abc").unwrap();
+        assert_eq!(matches, vec![b"" as &[u8]]);
+        assert_eq!(rest, b"abc");
+    }
 }